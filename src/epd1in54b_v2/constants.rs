@@ -0,0 +1,72 @@
+//! Waveform look-up tables for the SSD1680-class controller used by the
+//! Epd1in54b (v2).
+//!
+//! These are uploaded with `Command::WriteLutRegister` ahead of
+//! `MasterActivation` so the controller drives the panel from RAM instead of
+//! its OTP default. `LUT_FULL_UPDATE` is a full black/white + red waveform
+//! suitable for any content change; `LUT_QUICK_UPDATE` trades grayscale
+//! accuracy for speed and only defines a monochrome waveform, so it must not
+//! be paired with a chromatic (red) update.
+
+/// Full-refresh waveform LUT (black/white + red planes).
+#[rustfmt::skip]
+pub(crate) const LUT_FULL_UPDATE: [u8; 166] = [
+    0x80, 0x60, 0x40, 0x00, 0x00, 0x00, 0x00,
+    0x10, 0x60, 0x20, 0x00, 0x00, 0x00, 0x00,
+    0x80, 0x60, 0x40, 0x00, 0x00, 0x00, 0x00,
+    0x10, 0x60, 0x20, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x14, 0x08, 0x00, 0x00, 0x00, 0x00, 0x01,
+    0x0A, 0x0A, 0x00, 0x00, 0x00, 0x01,
+    0x0A, 0x0A, 0x00, 0x00, 0x00, 0x01,
+    0x14, 0x08, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x00, 0x00, 0x00,
+    0x22, 0x17, 0x41, 0x00, 0x32, 0x32,
+];
+
+/// Fast, monochrome-only partial-refresh waveform LUT.
+///
+/// Shorter frame count than [`LUT_FULL_UPDATE`] in exchange for speed; it
+/// only drives the black/white plane, so callers must stick to
+/// `update_frame`/`update_partial_frame` (never `update_color_frame`) while
+/// this LUT is active.
+#[rustfmt::skip]
+pub(crate) const LUT_QUICK_UPDATE: [u8; 166] = [
+    0x80, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x90, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    0x0A, 0x00, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x0A, 0x0A,
+];
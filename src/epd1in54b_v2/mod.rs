@@ -35,10 +35,141 @@ pub type Display1in54b = crate::graphics::Display<
     TriColor,
 >;
 
+/// Rotation of the image written to the panel.
+///
+/// Reprograms the 3 low bits of `DataEntryModeSetting` (X/Y address-counter
+/// increment direction and counter-update order), which is how this
+/// controller mounts the image without the host having to pre-rotate its
+/// framebuffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DisplayRotation {
+    /// No rotation, the panel's native orientation.
+    #[default]
+    Rotate0,
+    /// Rotate by 90 degrees clockwise.
+    Rotate90,
+    /// Rotate by 180 degrees clockwise.
+    Rotate180,
+    /// Rotate by 270 degrees clockwise.
+    Rotate270,
+}
+
+/// Width of the addressable frame for a given [`DisplayRotation`].
+fn rotated_width(rotation: DisplayRotation) -> u32 {
+    match rotation {
+        DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => HEIGHT,
+        DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => WIDTH,
+    }
+}
+
+/// Height of the addressable frame for a given [`DisplayRotation`].
+fn rotated_height(rotation: DisplayRotation) -> u32 {
+    match rotation {
+        DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => WIDTH,
+        DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => HEIGHT,
+    }
+}
+
+/// `DataEntryModeSetting` value for the given rotation: the low 3 bits
+/// select the X/Y address-counter increment direction and whether the
+/// counter advances in X or Y first.
+fn data_entry_mode(rotation: DisplayRotation) -> u8 {
+    match rotation {
+        DisplayRotation::Rotate0 => 0x03,
+        DisplayRotation::Rotate90 => 0x06,
+        DisplayRotation::Rotate180 => 0x00,
+        DisplayRotation::Rotate270 => 0x05,
+    }
+}
+
+/// Encodes a Celsius temperature as the 12-bit signed value
+/// `TemperatureSensorControl` expects, low byte first.
+fn encode_temperature(temp_celsius: i16) -> [u8; 2] {
+    let raw = (temp_celsius as i32) & 0x0FFF;
+    [raw as u8, (raw >> 8) as u8]
+}
+
+/// `DisplayUpdateControl2` value `display_frame` should issue, given the
+/// current update mode: a full update (`refresh_lut`), or a partial update
+/// with or without a base image loaded into the second RAM bank to diff
+/// against.
+fn display_update_mode(
+    partial_refresh: bool,
+    has_base_frame: bool,
+    refresh_lut: Option<RefreshLut>,
+) -> u8 {
+    if partial_refresh {
+        if has_base_frame {
+            // Differential update: compute the waveform from the
+            // difference against the base image held in the second RAM
+            // bank instead of a full black/white inversion.
+            0xCC
+        } else {
+            0xCF
+        }
+    } else {
+        match refresh_lut {
+            Some(RefreshLut::Quick) => 0xCF,
+            _ => 0xF7,
+        }
+    }
+}
+
+/// Picks the LUT `set_lut` should actually load, downgrading a requested
+/// `Quick` refresh to `Full` when the background is chromatic - the quick
+/// waveform only defines a monochrome plane, so pairing it with a red
+/// background would drive that plane with garbage timings.
+fn resolve_refresh_lut(
+    requested: Option<RefreshLut>,
+    background_color: TriColor,
+) -> Option<RefreshLut> {
+    if requested == Some(RefreshLut::Quick) && background_color == TriColor::Chromatic {
+        Some(RefreshLut::Full)
+    } else {
+        requested
+    }
+}
+
+/// Maps a host-facing, rotation-aware `(x, y, width, height)` window to the
+/// physical RAM window `set_ram_area`/`set_ram_counter` need.
+///
+/// The controller's X/Y counters always address the panel in its native
+/// (`Rotate0`) orientation - `Rotate90`/`Rotate270` swap which host axis maps
+/// onto which RAM axis, so the window has to be transposed before it's sent.
+/// `Rotate0`/`Rotate180` don't change which axis is which, only the counting
+/// direction, which `data_entry_mode` already handles.
+fn to_physical_window(
+    rotation: DisplayRotation,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> (u32, u32, u32, u32) {
+    match rotation {
+        DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (x, y, width, height),
+        DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (y, x, height, width),
+    }
+}
+
+/// Rounds a `(x, width)` window out to the nearest byte boundary on both
+/// edges, since `SetRamXAddressStartEndPosition` only has byte resolution.
+/// Returns the new `(x, width)`; the caller's window is always fully
+/// contained in the result.
+fn align_window(x: u32, width: u32) -> (u32, u32) {
+    let end = x + width;
+    let x = x & !7;
+    let width = (end - x + 7) & !7;
+    (x, width)
+}
+
 /// Epd1in54b driver
 pub struct Epd1in54b<SPI, BUSY, DC, RST, DELAY> {
     interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
     background_color: TriColor,
+    refresh_lut: Option<RefreshLut>,
+    partial_refresh: bool,
+    rotation: DisplayRotation,
+    has_base_frame: bool,
 }
 
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
@@ -79,8 +210,11 @@ where
             &[(HEIGHT - 1) as u8, 0x00, 0x00],
         )?; 
 
-        self
-            .cmd_with_data(spi, Command::DataEntryModeSetting, &[0x3])?;
+        self.cmd_with_data(
+            spi,
+            Command::DataEntryModeSetting,
+            &[data_entry_mode(self.rotation)],
+        )?;
 
         self.set_ram_area(spi, delay, 0, 0, WIDTH - 1, HEIGHT - 1)?;
 
@@ -96,7 +230,8 @@ where
             .cmd_with_data(spi, Command::TemperatureSensorControl, &[0xB1, 0x20])?;
 
         // load waveform LUT
-
+        let refresh_lut = self.refresh_lut;
+        self.set_lut(spi, delay, refresh_lut)?;
 
         self.set_ram_counter(spi, delay, 0, 0)?;
 
@@ -148,9 +283,25 @@ where
         chromatic: &[u8],
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
+
+        // set_lut only validated the quick/chromatic pairing against the
+        // background color at the time it was called - set_background_color
+        // may have switched to a chromatic background since, leaving a
+        // monochrome-only LUT loaded. Re-check now, right before the red
+        // plane actually gets written.
+        if resolve_refresh_lut(self.refresh_lut, self.background_color) != self.refresh_lut {
+            let refresh_lut = self.refresh_lut;
+            self.set_lut(spi, delay, refresh_lut)?;
+        }
+
         self.use_full_frame(spi, delay)?;
         self.cmd_with_data(spi, Command::WriteRam2, chromatic)?;
 
+        // This just overwrote the second RAM bank with real chromatic
+        // pixel data, not a monochrome base image - any base frame a
+        // partial update was planning to diff against is gone.
+        self.has_base_frame = false;
+
         Ok(())
     }
 }
@@ -179,7 +330,14 @@ where
         let interface = DisplayInterface::new(busy, dc, rst, delay_us);
         let color = DEFAULT_BACKGROUND_COLOR;
 
-        let mut epd = Epd1in54b { interface, background_color: color };
+        let mut epd = Epd1in54b {
+            interface,
+            background_color: color,
+            refresh_lut: None,
+            partial_refresh: false,
+            rotation: DisplayRotation::Rotate0,
+            has_base_frame: false,
+        };
 
         epd.init(spi, delay)?;
 
@@ -206,11 +364,11 @@ where
     }
 
     fn width(&self) -> u32 {
-        WIDTH
+        self.effective_width()
     }
 
     fn height(&self) -> u32 {
-        HEIGHT
+        self.effective_height()
     }
 
     fn update_frame(
@@ -222,10 +380,11 @@ where
         self.wait_until_idle(spi, delay)?;
         self.use_full_frame(spi, delay)?;
         self.cmd_with_data(spi, Command::WriteRam, buffer)?;
+        self.partial_refresh = false;
+        self.has_base_frame = false;
         Ok(())
     }
 
-    #[allow(unused)]
     fn update_partial_frame(
         &mut self,
         spi: &mut SPI,
@@ -236,7 +395,17 @@ where
         width: u32,
         height: u32,
     ) -> Result<(), SPI::Error> {
-        unimplemented!()
+        let (x, y, width, height) = to_physical_window(self.rotation, x, y, width, height);
+        let (x, width) = align_window(x, width);
+
+        self.wait_until_idle(spi, delay)?;
+        self.set_ram_area(spi, delay, x, y, x + width - 1, y + height - 1)?;
+        self.set_ram_counter(spi, delay, x, y)?;
+        self.cmd_with_data(spi, Command::WriteRam, buffer)?;
+
+        self.partial_refresh = true;
+
+        Ok(())
     }
 
     fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -244,7 +413,9 @@ where
         self.wait_until_idle(spi, delay)?;
 
         //F7 which is also TEMP is used by the arduino version, C7 was used by the rust version
-        self.cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xF7])?;
+        let update_mode =
+            display_update_mode(self.partial_refresh, self.has_base_frame, self.refresh_lut);
+        self.cmd_with_data(spi, Command::DisplayUpdateControl2, &[update_mode])?;
 
         self.command(spi, Command::MasterActivation)?;
         // MASTER Activation should not be interupted to avoid corruption of panel images
@@ -291,17 +462,38 @@ where
                 .data_x_times(spi, 0x00, WIDTH / 8 * HEIGHT)?;
         }
 
+        self.partial_refresh = false;
+        self.has_base_frame = false;
+
         Ok(())
     }
 
 
     fn set_lut(
         &mut self,
-        _spi: &mut SPI,
+        spi: &mut SPI,
         _delay: &mut DELAY,
-        _refresh_rate: Option<RefreshLut>,
+        refresh_rate: Option<RefreshLut>,
     ) -> Result<(), SPI::Error> {
-        unimplemented!();
+        let refresh_rate = resolve_refresh_lut(refresh_rate, self.background_color);
+
+        match refresh_rate {
+            Some(RefreshLut::Quick) => {
+                self.cmd_with_data(spi, Command::WriteLutRegister, &LUT_QUICK_UPDATE)?;
+            }
+            Some(RefreshLut::Full) => {
+                self.cmd_with_data(spi, Command::WriteLutRegister, &LUT_FULL_UPDATE)?;
+            }
+            None => {
+                // Nothing to upload - the controller keeps driving from
+                // whatever waveform (OTP default or a previously uploaded
+                // LUT) is already latched in, and display_frame picks the
+                // matching DisplayUpdateControl2 mode from self.refresh_lut.
+            }
+        }
+
+        self.refresh_lut = refresh_rate;
+        Ok(())
     }
 
     fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
@@ -340,13 +532,121 @@ where
         spi: &mut SPI,
         delay: &mut DELAY,
     ) -> Result<(), SPI::Error> {
-        // choose full frame/ram
+        // choose full frame/ram - RAM addressing is always in the panel's
+        // native (Rotate0) orientation, regardless of self.rotation.
         self.set_ram_area(spi, delay, 0, 0, WIDTH - 1, HEIGHT - 1)?;
 
         // start from the beginning
         self.set_ram_counter(spi, delay, 0, 0)
     }
 
+    /// Width of the addressable frame as seen by the host, accounting for
+    /// [`DisplayRotation`].
+    fn effective_width(&self) -> u32 {
+        rotated_width(self.rotation)
+    }
+
+    /// Height of the addressable frame as seen by the host, accounting for
+    /// [`DisplayRotation`].
+    fn effective_height(&self) -> u32 {
+        rotated_height(self.rotation)
+    }
+
+    /// Rotates the panel's image without requiring the host to pre-rotate
+    /// its framebuffer.
+    ///
+    /// Takes effect immediately: reprograms `DataEntryModeSetting` and
+    /// re-addresses the full RAM window so subsequent `width()`/`height()`
+    /// and frame writes line up with the new orientation.
+    pub fn set_rotation(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        rotation: DisplayRotation,
+    ) -> Result<(), SPI::Error> {
+        self.rotation = rotation;
+
+        self.cmd_with_data(
+            spi,
+            Command::DataEntryModeSetting,
+            &[data_entry_mode(rotation)],
+        )?;
+
+        self.use_full_frame(spi, delay)
+    }
+
+    /// Current [`DisplayRotation`].
+    pub fn rotation(&self) -> DisplayRotation {
+        self.rotation
+    }
+
+    /// Compensates the waveform generator against an externally measured
+    /// temperature instead of the panel's internal sensor.
+    ///
+    /// E-ink waveforms are strongly temperature dependent; in enclosures
+    /// that run far from room temperature the internal sensor's reading can
+    /// be off enough to cause ghosting. This switches
+    /// `TemperatureSensorSelection` to external mode and writes
+    /// `temp_celsius` into `TemperatureSensorControl` so the next
+    /// `display_frame` compensates against it. Internal-sensor mode is
+    /// restored on the next `init`/`wake_up`.
+    pub fn set_temperature(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        temp_celsius: i16,
+    ) -> Result<(), SPI::Error> {
+        self.wait_until_idle(spi, delay)?;
+
+        self.cmd_with_data(
+            spi,
+            Command::TemperatureSensorSelection,
+            &[0x48], // 0x48: external temperature sensor
+        )?;
+
+        self.cmd_with_data(
+            spi,
+            Command::TemperatureSensorControl,
+            &encode_temperature(temp_celsius),
+        )?;
+
+        Ok(())
+    }
+
+    /// Writes the previously displayed monochrome image into the
+    /// controller's second RAM bank so a later partial `display_frame` can
+    /// drive a differential waveform against it instead of fully inverting
+    /// black/white on every update.
+    ///
+    /// Call this with the same buffer and `x`/`y`/`width`/`height` window
+    /// passed to `update_frame` (pass `0, 0, WIDTH, HEIGHT` for a full-frame
+    /// base) or `update_partial_frame`, after `display_frame`, so the base
+    /// image stays one frame behind what's on the panel - the driver
+    /// doesn't keep its own copy of the framebuffer to stay `no_std`-friendly.
+    /// Never calling it simply keeps partial updates using full inversions.
+    pub fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        old_buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), SPI::Error> {
+        let (x, y, width, height) = to_physical_window(self.rotation, x, y, width, height);
+        let (x, width) = align_window(x, width);
+
+        self.wait_until_idle(spi, delay)?;
+        self.set_ram_area(spi, delay, x, y, x + width - 1, y + height - 1)?;
+        self.set_ram_counter(spi, delay, x, y)?;
+        self.cmd_with_data(spi, Command::WriteRam2, old_buffer)?;
+
+        self.has_base_frame = true;
+
+        Ok(())
+    }
+
     pub(crate) fn set_ram_area(
         &mut self,
         spi: &mut SPI,
@@ -357,8 +657,10 @@ where
         end_y: u32,
     ) -> Result<(), SPI::Error> {
         self.wait_until_idle(spi, delay)?;
-        assert!(start_x < end_x);
-        assert!(start_y < end_y);
+        // `<=` rather than `<`: a single-row/column partial window (e.g. a
+        // one-line status field) has start == end on that axis.
+        assert!(start_x <= end_x);
+        assert!(start_y <= end_y);
 
         // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
         // aren't relevant
@@ -416,4 +718,104 @@ mod tests {
         assert_eq!(HEIGHT, 200);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, TriColor::White);
     }
+
+    #[test]
+    fn align_window_rounds_out_to_byte_boundaries() {
+        // x=3, width=10 wants pixels 3..=12: rounds out to the byte pair 0..=15
+        assert_eq!(align_window(3, 10), (0, 16));
+        // x=7, width=8 wants pixels 7..=14: rounds out to the byte pair 0..=15
+        assert_eq!(align_window(7, 8), (0, 16));
+        // already byte-aligned on both edges: no rounding needed
+        assert_eq!(align_window(0, 8), (0, 8));
+        assert_eq!(align_window(8, 8), (8, 8));
+    }
+
+    #[test]
+    fn update_partial_frame_pipeline_transposes_then_aligns_for_rotated_window() {
+        // The exact pipeline update_partial_frame/update_old_frame run: first
+        // map the caller's logical window onto physical RAM axes, then round
+        // the physical x/width out to byte boundaries.
+        let (x, y, width, height) = to_physical_window(DisplayRotation::Rotate90, 3, 5, 10, 4);
+        assert_eq!((x, y, width, height), (5, 3, 4, 10));
+        let (x, width) = align_window(x, width);
+        // physical x=5, width=4 wants pixels 5..=8: rounds out to 0..=15
+        assert_eq!((x, width), (0, 16));
+    }
+
+    #[test]
+    fn rotated_dims_swap_on_90_and_270() {
+        assert_eq!(rotated_width(DisplayRotation::Rotate0), WIDTH);
+        assert_eq!(rotated_height(DisplayRotation::Rotate0), HEIGHT);
+        assert_eq!(rotated_width(DisplayRotation::Rotate180), WIDTH);
+        assert_eq!(rotated_height(DisplayRotation::Rotate180), HEIGHT);
+        assert_eq!(rotated_width(DisplayRotation::Rotate90), HEIGHT);
+        assert_eq!(rotated_height(DisplayRotation::Rotate90), WIDTH);
+        assert_eq!(rotated_width(DisplayRotation::Rotate270), HEIGHT);
+        assert_eq!(rotated_height(DisplayRotation::Rotate270), WIDTH);
+    }
+
+    #[test]
+    fn data_entry_mode_is_distinct_per_rotation() {
+        assert_eq!(data_entry_mode(DisplayRotation::Rotate0), 0x03);
+        assert_eq!(data_entry_mode(DisplayRotation::Rotate90), 0x06);
+        assert_eq!(data_entry_mode(DisplayRotation::Rotate180), 0x00);
+        assert_eq!(data_entry_mode(DisplayRotation::Rotate270), 0x05);
+    }
+
+    #[test]
+    fn encode_temperature_is_12_bit_twos_complement() {
+        assert_eq!(encode_temperature(25), [0x19, 0x00]);
+        assert_eq!(encode_temperature(-10), [0xF6, 0x0F]);
+    }
+
+    #[test]
+    fn to_physical_window_transposes_axes_on_90_and_270() {
+        assert_eq!(
+            to_physical_window(DisplayRotation::Rotate0, 3, 5, 10, 20),
+            (3, 5, 10, 20)
+        );
+        assert_eq!(
+            to_physical_window(DisplayRotation::Rotate180, 3, 5, 10, 20),
+            (3, 5, 10, 20)
+        );
+        assert_eq!(
+            to_physical_window(DisplayRotation::Rotate90, 3, 5, 10, 20),
+            (5, 3, 20, 10)
+        );
+        assert_eq!(
+            to_physical_window(DisplayRotation::Rotate270, 3, 5, 10, 20),
+            (5, 3, 20, 10)
+        );
+    }
+
+    #[test]
+    fn resolve_refresh_lut_downgrades_quick_on_chromatic_background() {
+        assert_eq!(
+            resolve_refresh_lut(Some(RefreshLut::Quick), TriColor::Chromatic),
+            Some(RefreshLut::Full)
+        );
+        assert_eq!(
+            resolve_refresh_lut(Some(RefreshLut::Quick), TriColor::Black),
+            Some(RefreshLut::Quick)
+        );
+        assert_eq!(
+            resolve_refresh_lut(Some(RefreshLut::Full), TriColor::Chromatic),
+            Some(RefreshLut::Full)
+        );
+        assert_eq!(resolve_refresh_lut(None, TriColor::Chromatic), None);
+    }
+
+    #[test]
+    fn display_update_mode_falls_back_to_full_inversion_without_base_frame() {
+        // update_old_frame, then display_frame: a base image is loaded, so
+        // the first partial update after it is a differential one.
+        assert_eq!(display_update_mode(true, true, None), 0xCC);
+        // A plain full update/display in between must invalidate the base
+        // frame (update_frame/update_chromatic_frame clear has_base_frame).
+        assert_eq!(display_update_mode(false, false, None), 0xF7);
+        // The following partial update no longer has a base frame to diff
+        // against - it must fall back to a full inversion, not 0xCC against
+        // stale RAM2 bytes.
+        assert_eq!(display_update_mode(true, false, None), 0xCF);
+    }
 }